@@ -10,13 +10,15 @@
 //! fn main() {
 //!     let snowflake: SnowFlake = SnowFlake::new(1, 1)
 //!         .expect("Datacenter or machine id is too big.");
-//!     let id: u64 = snowflake.generate_id();
+//!     let id: u64 = snowflake.generate_id().expect("Clock is not behaving.");
 //!     // Use the generated id to persist any record
 //! }
 //! ```
 
 use anyhow::{anyhow, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -30,58 +32,431 @@ pub const MAX_DATACENTER_ID: u64 = (1 << DATACENTER_ID_BITS) - 1;
 pub const MAX_MACHINE_ID: u64 = (1 << MACHINE_ID_BITS) - 1;
 pub const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
 
-/// Struct containing the datacenter-, machine id and atomic sequence used to
-/// generate the twitter snowflake id.
+/// A snowflake id broken back down into its component parts.
+///
+/// Returned by [`decode_id`] / [`SnowFlake::decode`] when reversing an id
+/// produced by [`SnowFlake::generate_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedSnowflake {
+    pub timestamp_ms: u128,
+    pub datacenter_id: u64,
+    pub machine_id: u64,
+    pub sequence: u64,
+}
+
+/// Unpack a snowflake id generated by [`SnowFlake::generate_id`] back into its
+/// timestamp, datacenter id, machine id and sequence.
+///
+/// The timestamp is returned as an absolute unix millisecond value, i.e.
+/// [`EPOCH_START`] has already been added back.
+///
+/// This only decodes against the default 3/7/12 bit layout and
+/// [`EPOCH_START`] epoch. For a `SnowFlake` built with a custom
+/// [`SnowFlakeBuilder`] layout or epoch, use [`SnowFlake::decode`] instead —
+/// this function will silently return wrong fields for such ids.
+pub fn decode_id(id: u64) -> DecodedSnowflake {
+    let timestamp_ms = (id >> (DATACENTER_ID_BITS + MACHINE_ID_BITS + SEQUENCE_BITS)) as u128
+        + EPOCH_START;
+    let datacenter_id = (id >> (MACHINE_ID_BITS + SEQUENCE_BITS)) & MAX_DATACENTER_ID;
+    let machine_id = (id >> SEQUENCE_BITS) & MAX_MACHINE_ID;
+    let sequence = id & MAX_SEQUENCE;
+
+    DecodedSnowflake {
+        timestamp_ms,
+        datacenter_id,
+        machine_id,
+        sequence,
+    }
+}
+
+/// The derived bit-layout bounds shared by [`SnowFlakeBuilder::build`] and
+/// `SnowFlake`'s [`Deserialize`] impl.
+struct LayoutBounds {
+    max_datacenter_id: u64,
+    max_machine_id: u64,
+    max_sequence: u64,
+    timestamp_bits: u32,
+}
+
+/// Validate a bit layout and datacenter/machine id pair, used both when
+/// building a fresh [`SnowFlake`] and when reconstructing one from
+/// untrusted/persisted state, so neither path can skip a check the other
+/// relies on.
+fn validate_layout(
+    datacenter_id: u64,
+    machine_id: u64,
+    datacenter_id_bits: u32,
+    machine_id_bits: u32,
+    sequence_bits: u32,
+) -> Result<LayoutBounds, Error> {
+    for (name, bits) in [
+        ("datacenter_id_bits", datacenter_id_bits),
+        ("machine_id_bits", machine_id_bits),
+        ("sequence_bits", sequence_bits),
+    ] {
+        if bits > 63 {
+            return Err(anyhow!("{} must be at most 63, got {}", name, bits));
+        }
+    }
+
+    if sequence_bits == 0 {
+        return Err(anyhow!(
+            "sequence_bits must be at least 1, a zero-width sequence can't represent the first id minted in a millisecond"
+        ));
+    }
+
+    let total_bits = datacenter_id_bits
+        .checked_add(machine_id_bits)
+        .and_then(|sum| sum.checked_add(sequence_bits))
+        .ok_or_else(|| anyhow!("datacenter_id_bits + machine_id_bits + sequence_bits overflowed"))?;
+    if total_bits > 63 {
+        return Err(anyhow!(
+            "datacenter_id_bits + machine_id_bits + sequence_bits must be at most 63, got {}",
+            total_bits
+        ));
+    }
+
+    let max_datacenter_id = (1 << datacenter_id_bits) - 1;
+    let max_machine_id = (1 << machine_id_bits) - 1;
+    let max_sequence = (1 << sequence_bits) - 1;
+
+    if datacenter_id > max_datacenter_id {
+        return Err(anyhow!("Datacenter id must be at most {}", max_datacenter_id));
+    }
+
+    if machine_id > max_machine_id {
+        return Err(anyhow!("Machine id must be at most {}", max_machine_id));
+    }
+
+    Ok(LayoutBounds {
+        max_datacenter_id,
+        max_machine_id,
+        max_sequence,
+        timestamp_bits: 63 - total_bits,
+    })
+}
+
+/// Builds a [`SnowFlake`] with a non-default bit layout and/or epoch.
+///
+/// By default a builder matches [`SnowFlake::new`]: the stock 3/7/12 bit
+/// split and the [`EPOCH_START`] epoch. Use [`Self::datacenter_id_bits`],
+/// [`Self::machine_id_bits`], [`Self::sequence_bits`] and [`Self::epoch`] to
+/// retune the layout for a specific deployment, e.g. to match a foreign
+/// schema or another vendor's epoch. Use [`Self::infallible`] to keep the
+/// generator alive past the point where the timestamp would otherwise
+/// overflow.
+///
+/// # Example
+/// ```
+/// use qanik::SnowFlakeBuilder;
+///
+/// let snowflake = SnowFlakeBuilder::new(1, 1)
+///     .datacenter_id_bits(5)
+///     .machine_id_bits(5)
+///     .sequence_bits(12)
+///     .build()
+///     .expect("Datacenter or machine id is too big.");
+/// let id: u64 = snowflake.generate_id().expect("Clock is not behaving.");
+/// ```
+pub struct SnowFlakeBuilder {
+    datacenter_id: u64,
+    machine_id: u64,
+    datacenter_id_bits: u32,
+    machine_id_bits: u32,
+    sequence_bits: u32,
+    epoch: SystemTime,
+    infallible: bool,
+}
+
+impl SnowFlakeBuilder {
+    /// Start building a SnowFlake for a datacenter and machine, using the
+    /// default bit layout and epoch until overridden.
+    pub fn new(datacenter_id: u64, machine_id: u64) -> Self {
+        Self {
+            datacenter_id,
+            machine_id,
+            datacenter_id_bits: DATACENTER_ID_BITS,
+            machine_id_bits: MACHINE_ID_BITS,
+            sequence_bits: SEQUENCE_BITS,
+            epoch: UNIX_EPOCH + Duration::from_millis(EPOCH_START as u64),
+            infallible: false,
+        }
+    }
+
+    /// Override the number of bits reserved for the datacenter id.
+    pub fn datacenter_id_bits(mut self, bits: u32) -> Self {
+        self.datacenter_id_bits = bits;
+        self
+    }
+
+    /// Override the number of bits reserved for the machine id.
+    pub fn machine_id_bits(mut self, bits: u32) -> Self {
+        self.machine_id_bits = bits;
+        self
+    }
+
+    /// Override the number of bits reserved for the per-millisecond sequence.
+    pub fn sequence_bits(mut self, bits: u32) -> Self {
+        self.sequence_bits = bits;
+        self
+    }
+
+    /// Override the epoch that timestamps are measured from.
+    pub fn epoch(mut self, epoch: SystemTime) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Make the resulting [`SnowFlake`] rebase its epoch instead of
+    /// overflowing once the elapsed time since the epoch no longer fits in
+    /// the timestamp's bit budget.
+    ///
+    /// IDs minted after a rebase are no longer globally time-sortable
+    /// against ones minted before it, but remain unique for as long as the
+    /// datacenter/machine pair stays stable.
+    pub fn infallible(mut self) -> Self {
+        self.infallible = true;
+        self
+    }
+
+    /// Validate the layout and build the [`SnowFlake`].
+    pub fn build(self) -> Result<SnowFlake, Error> {
+        let bounds = validate_layout(
+            self.datacenter_id,
+            self.machine_id,
+            self.datacenter_id_bits,
+            self.machine_id_bits,
+            self.sequence_bits,
+        )?;
+
+        if self.epoch > SystemTime::now() {
+            return Err(anyhow!("epoch must not be after the current time"));
+        }
+
+        let epoch_start = self
+            .epoch
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| anyhow!("epoch must not be before the unix epoch"))?
+            .as_millis() as u64;
+
+        Ok(SnowFlake {
+            datacenter_id: self.datacenter_id,
+            machine_id: self.machine_id,
+            state: Mutex::new(GeneratorState {
+                last_timestamp: 0,
+                sequence: 1,
+            }),
+            datacenter_id_bits: self.datacenter_id_bits,
+            machine_id_bits: self.machine_id_bits,
+            sequence_bits: self.sequence_bits,
+            timestamp_bits: bounds.timestamp_bits,
+            max_datacenter_id: bounds.max_datacenter_id,
+            max_machine_id: bounds.max_machine_id,
+            max_sequence: bounds.max_sequence,
+            epoch_start: AtomicU64::new(epoch_start),
+            infallible: self.infallible,
+        })
+    }
+}
+
+/// The current unix timestamp in milliseconds.
+fn current_millis() -> Result<u64, Error> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| anyhow!("System clock is set before the unix epoch"))?
+        .as_millis() as u64)
+}
+
+/// The last millisecond an id was minted for and the sequence handed out
+/// within it, guarded together so a reader can never observe one updated
+/// without the other.
+struct GeneratorState {
+    last_timestamp: u64,
+    sequence: u64,
+}
+
+/// Struct containing the datacenter-, machine id and generation state used
+/// to generate the twitter snowflake id, along with the bit layout and
+/// epoch it was built with.
+///
+/// Implements `Serialize`/`Deserialize` so a generator's configuration and
+/// current sequence/last-timestamp position can be snapshotted and resumed
+/// without risking sequence reuse within the same millisecond after a
+/// restart.
 pub struct SnowFlake {
     datacenter_id: u64,
     machine_id: u64,
-    sequence: AtomicU64,
+    state: Mutex<GeneratorState>,
+    datacenter_id_bits: u32,
+    machine_id_bits: u32,
+    sequence_bits: u32,
+    timestamp_bits: u32,
+    max_datacenter_id: u64,
+    max_machine_id: u64,
+    max_sequence: u64,
+    epoch_start: AtomicU64,
+    infallible: bool,
 }
 
 impl SnowFlake {
-    /// Create a new SnowFlake instance with for a datacenter and machine.
+    /// Create a new SnowFlake instance with for a datacenter and machine,
+    /// using the default 3/7/12 bit layout and [`EPOCH_START`] epoch. Use
+    /// [`SnowFlakeBuilder`] to customize either.
     pub fn new(datacenter_id: u64, machine_id: u64) -> Result<SnowFlake, Error> {
-        if datacenter_id > MAX_DATACENTER_ID {
-            return Err(anyhow!(
-                "Datacenter id must be less than {}",
-                MAX_DATACENTER_ID
-            ));
-        }
+        SnowFlakeBuilder::new(datacenter_id, machine_id).build()
+    }
+
+    /// Generate a new snowflake id in the sequence for the current timestamp, datacenter and machine.
+    ///
+    /// Returns an error if the system clock has moved backwards since the
+    /// last generated id (e.g. an NTP correction or a VM migration), since
+    /// generating an id in that case would risk a duplicate or
+    /// out-of-order value.
+    ///
+    /// If the builder's [`SnowFlakeBuilder::infallible`] mode is off and the
+    /// elapsed time since the epoch overflows the timestamp's bit budget,
+    /// the generated id silently wraps; turn infallible mode on to rebase
+    /// the epoch instead.
+    pub fn generate_id(&self) -> Result<u64, Error> {
+        loop {
+            let timestamp = current_millis()?;
+
+            // Hold the lock for the whole read-decide-update sequence so two
+            // threads can never both observe the same `last_timestamp` and
+            // each think they're first to mint an id for it.
+            let mut state = self.state.lock().expect("SnowFlake state mutex poisoned");
+
+            if timestamp < state.last_timestamp {
+                return Err(anyhow!(
+                    "Clock moved backwards by {} ms, refusing to generate an id",
+                    state.last_timestamp - timestamp
+                ));
+            }
+
+            let sequence = if timestamp == state.last_timestamp {
+                let seq = state.sequence & self.max_sequence;
+                if seq == 0 {
+                    // Sequence exhausted for this millisecond, wait for the next one.
+                    drop(state);
+                    thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+                state.sequence += 1;
+                seq
+            } else {
+                state.sequence = 2;
+                1
+            };
+
+            state.last_timestamp = timestamp;
 
-        if machine_id > MAX_MACHINE_ID {
-            return Err(anyhow!("Machine id must be less than {}", MAX_MACHINE_ID));
+            let epoch_start = self.epoch_start.load(Ordering::SeqCst);
+            let mut elapsed = timestamp - epoch_start;
+            if self.infallible && (elapsed >> self.timestamp_bits) != 0 {
+                // The elapsed time no longer fits in the timestamp's bit
+                // budget; rebase the epoch to keep minting valid, unique ids.
+                self.epoch_start.store(timestamp, Ordering::SeqCst);
+                elapsed = 0;
+            }
+            drop(state);
+
+            let mut id: u64 =
+                elapsed << (self.datacenter_id_bits + self.machine_id_bits + self.sequence_bits);
+            id = id
+                | (self.datacenter_id << (self.machine_id_bits + self.sequence_bits))
+                | (self.machine_id << self.sequence_bits)
+                | sequence;
+            return Ok(id);
         }
+    }
+
+    /// Unpack a snowflake id previously produced by [`Self::generate_id`] into
+    /// its timestamp, datacenter id, machine id and sequence, using this
+    /// instance's bit layout and *current* epoch.
+    ///
+    /// Note that after an infallible rebase this decodes against the
+    /// instance's latest epoch, so timestamps recovered from ids minted
+    /// before the rebase will not be meaningful.
+    pub fn decode(&self, id: u64) -> DecodedSnowflake {
+        let epoch_start = self.epoch_start.load(Ordering::SeqCst) as u128;
+        let timestamp_ms = (id >> (self.datacenter_id_bits + self.machine_id_bits + self.sequence_bits))
+            as u128
+            + epoch_start;
+        let datacenter_id = (id >> (self.machine_id_bits + self.sequence_bits)) & self.max_datacenter_id;
+        let machine_id = (id >> self.sequence_bits) & self.max_machine_id;
+        let sequence = id & self.max_sequence;
 
-        let sequence = AtomicU64::new(1);
-        Ok(Self {
+        DecodedSnowflake {
+            timestamp_ms,
             datacenter_id,
             machine_id,
             sequence,
-        })
+        }
     }
+}
+
+/// On-the-wire representation of a [`SnowFlake`]'s configuration and
+/// current generation position.
+#[derive(Serialize, Deserialize)]
+struct SnowFlakeState {
+    datacenter_id: u64,
+    machine_id: u64,
+    sequence: u64,
+    last_timestamp: u64,
+    datacenter_id_bits: u32,
+    machine_id_bits: u32,
+    sequence_bits: u32,
+    epoch_start: u64,
+    infallible: bool,
+}
 
-    /// Generate a new snowflake id in the sequence for the current timestamp, datacenter and machine
-    pub fn generate_id(&self) -> u64 {
-        let sequence: &AtomicU64 = &self.sequence;
-        let current = sequence.fetch_add(1, Ordering::Relaxed);
-        sequence.compare_exchange(MAX_SEQUENCE, 1, Ordering::SeqCst, Ordering::Relaxed).unwrap_or_else(| e | e);
-        if current == MAX_SEQUENCE {
-            thread::sleep(Duration::from_millis(1))    
+impl Serialize for SnowFlake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let state = self.state.lock().expect("SnowFlake state mutex poisoned");
+        SnowFlakeState {
+            datacenter_id: self.datacenter_id,
+            machine_id: self.machine_id,
+            sequence: state.sequence,
+            last_timestamp: state.last_timestamp,
+            datacenter_id_bits: self.datacenter_id_bits,
+            machine_id_bits: self.machine_id_bits,
+            sequence_bits: self.sequence_bits,
+            epoch_start: self.epoch_start.load(Ordering::SeqCst),
+            infallible: self.infallible,
         }
-        
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went to heck!")
-            .as_millis();
-
-        let mut id: u64 = ((timestamp - EPOCH_START)
-            << (DATACENTER_ID_BITS + MACHINE_ID_BITS + SEQUENCE_BITS))
-            as u64;
-        id = id
-            | (self.datacenter_id << (MACHINE_ID_BITS + SEQUENCE_BITS))
-            | (self.machine_id << SEQUENCE_BITS)
-            | current;
-        id
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SnowFlake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = SnowFlakeState::deserialize(deserializer)?;
+        let bounds = validate_layout(
+            state.datacenter_id,
+            state.machine_id,
+            state.datacenter_id_bits,
+            state.machine_id_bits,
+            state.sequence_bits,
+        )
+        .map_err(serde::de::Error::custom)?;
+
+        Ok(SnowFlake {
+            datacenter_id: state.datacenter_id,
+            machine_id: state.machine_id,
+            state: Mutex::new(GeneratorState {
+                last_timestamp: state.last_timestamp,
+                sequence: state.sequence,
+            }),
+            datacenter_id_bits: state.datacenter_id_bits,
+            machine_id_bits: state.machine_id_bits,
+            sequence_bits: state.sequence_bits,
+            timestamp_bits: bounds.timestamp_bits,
+            max_datacenter_id: bounds.max_datacenter_id,
+            max_machine_id: bounds.max_machine_id,
+            max_sequence: bounds.max_sequence,
+            epoch_start: AtomicU64::new(state.epoch_start),
+            infallible: state.infallible,
+        })
     }
 }
 
@@ -103,10 +478,200 @@ mod tests {
     #[test]
     fn check_generated_id() {
         let snowflake = SnowFlake::new(1, 1).expect("Something went wrong...");
-        let id = snowflake.generate_id();
+        let id = snowflake.generate_id().expect("Clock is not behaving.");
 
         assert_eq!(id & 1, 1);
         assert_eq!(id >> SEQUENCE_BITS & 1, 1);
         assert_eq!(id >> (SEQUENCE_BITS + MACHINE_ID_BITS) & 1, 1);
     }
+
+    #[test]
+    fn decode_roundtrips_generated_id() {
+        let snowflake = SnowFlake::new(1, 1).expect("Something went wrong...");
+        let id = snowflake.generate_id().expect("Clock is not behaving.");
+        let decoded = snowflake.decode(id);
+
+        assert_eq!(decoded.datacenter_id, 1);
+        assert_eq!(decoded.machine_id, 1);
+        assert!(decoded.timestamp_ms >= EPOCH_START);
+    }
+
+    #[test]
+    fn builder_rejects_layout_over_63_bits() {
+        let result = SnowFlakeBuilder::new(1, 1)
+            .datacenter_id_bits(32)
+            .machine_id_bits(32)
+            .sequence_bits(12)
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_rejects_a_single_field_over_63_bits_without_overflowing() {
+        let result = SnowFlakeBuilder::new(1, 1)
+            .datacenter_id_bits(3_000_000_000)
+            .machine_id_bits(0)
+            .sequence_bits(0)
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_rejects_a_zero_width_sequence() {
+        let result = SnowFlakeBuilder::new(1, 4)
+            .datacenter_id_bits(3)
+            .machine_id_bits(7)
+            .sequence_bits(0)
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_rejects_an_epoch_after_now() {
+        let result = SnowFlakeBuilder::new(1, 1)
+            .epoch(SystemTime::now() + Duration::from_secs(3600))
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_generates_and_decodes_with_custom_layout() {
+        let snowflake = SnowFlakeBuilder::new(3, 5)
+            .datacenter_id_bits(5)
+            .machine_id_bits(5)
+            .sequence_bits(12)
+            .build()
+            .expect("Something went wrong...");
+
+        let id = snowflake.generate_id().expect("Clock is not behaving.");
+        let decoded = snowflake.decode(id);
+
+        assert_eq!(decoded.datacenter_id, 3);
+        assert_eq!(decoded.machine_id, 5);
+    }
+
+    #[test]
+    fn generate_id_errors_on_clock_regression() {
+        let snowflake = SnowFlake::new(1, 1).expect("Something went wrong...");
+        snowflake.state.lock().expect("lock").last_timestamp = u64::MAX;
+
+        let result = snowflake.generate_id();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn generate_id_is_monotonic_within_a_millisecond() {
+        let snowflake = SnowFlake::new(1, 1).expect("Something went wrong...");
+        let first = snowflake.generate_id().expect("Clock is not behaving.");
+        let second = snowflake.generate_id().expect("Clock is not behaving.");
+
+        assert!(second > first);
+    }
+
+    #[test]
+    fn infallible_mode_rebases_epoch_instead_of_overflowing() {
+        // Leaves zero bits for the timestamp, so any call in a millisecond
+        // after the epoch overflows and must rebase rather than wrap.
+        let snowflake = SnowFlakeBuilder::new(1, 1)
+            .datacenter_id_bits(30)
+            .machine_id_bits(30)
+            .sequence_bits(3)
+            .infallible()
+            .build()
+            .expect("Something went wrong...");
+
+        for _ in 0..5 {
+            snowflake
+                .generate_id()
+                .expect("infallible mode should never error on overflow");
+        }
+
+        assert!(snowflake.epoch_start.load(Ordering::SeqCst) > EPOCH_START as u64);
+    }
+
+    #[test]
+    fn snowflake_survives_a_serde_roundtrip() {
+        let snowflake = SnowFlake::new(2, 6).expect("Something went wrong...");
+        snowflake
+            .generate_id()
+            .expect("Clock is not behaving.");
+
+        let json = serde_json::to_string(&snowflake).expect("Failed to serialize");
+        let restored: SnowFlake = serde_json::from_str(&json).expect("Failed to deserialize");
+
+        assert_eq!(restored.datacenter_id, snowflake.datacenter_id);
+        assert_eq!(restored.machine_id, snowflake.machine_id);
+
+        let restored_state = restored.state.lock().expect("lock");
+        let original_state = snowflake.state.lock().expect("lock");
+        assert_eq!(restored_state.sequence, original_state.sequence);
+        assert_eq!(restored_state.last_timestamp, original_state.last_timestamp);
+    }
+
+    #[test]
+    fn deserialize_rejects_a_layout_over_63_bits() {
+        let json = r#"{
+            "datacenter_id": 0,
+            "machine_id": 0,
+            "sequence": 1,
+            "last_timestamp": 0,
+            "datacenter_id_bits": 100,
+            "machine_id_bits": 0,
+            "sequence_bits": 1,
+            "epoch_start": 0,
+            "infallible": false
+        }"#;
+
+        let result: Result<SnowFlake, _> = serde_json::from_str(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_rejects_an_out_of_range_datacenter_id() {
+        let json = r#"{
+            "datacenter_id": 999,
+            "machine_id": 0,
+            "sequence": 1,
+            "last_timestamp": 0,
+            "datacenter_id_bits": 3,
+            "machine_id_bits": 7,
+            "sequence_bits": 12,
+            "epoch_start": 0,
+            "infallible": false
+        }"#;
+
+        let result: Result<SnowFlake, _> = serde_json::from_str(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn generate_id_never_duplicates_under_concurrency() {
+        let snowflake = std::sync::Arc::new(SnowFlake::new(1, 1).expect("Something went wrong..."));
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let snowflake = std::sync::Arc::clone(&snowflake);
+            handles.push(thread::spawn(move || {
+                (0..200)
+                    .map(|_| snowflake.generate_id().expect("Clock is not behaving."))
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        let mut ids: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("generator thread panicked"))
+            .collect();
+        let total = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), total, "generate_id produced duplicate ids");
+    }
 }